@@ -1,5 +1,8 @@
+use std::borrow::Cow;
 use std::char;
+use std::collections::VecDeque;
 use std::io::{self, BufRead, Cursor, Write};
+use std::mem;
 
 use self::DecodeErrKind::*;
 use self::DecodeState::*;
@@ -96,6 +99,49 @@ pub fn decode_html_rw<R: BufRead, W: Write>(
     writer: &mut W,
     sloppy: bool,
 ) -> Result<(), DecodeErr> {
+    decode_html_rw_with(reader, writer, sloppy, false, |_: &str| None)
+}
+
+/// Decodes an entity-encoded string from a reader to a writer, consulting a custom resolver
+/// for named entities before falling back to the built-in `entities::ENTITIES` table.
+///
+/// `resolver` is called with the entity name found between `&` and `;` (eg. `"amp"` for
+/// `&amp;`) and may return a replacement. This lets callers support entities that aren't
+/// part of the standard HTML5 table, such as DTD-declared or application-specific entities,
+/// without having to pre-populate a `HashMap` for the common case where most input uses only
+/// the built-in entities.
+///
+/// When `html5` is set, decoding follows the WHATWG HTML5 parsing rules rather than the
+/// stricter rules used elsewhere in this crate:
+///
+/// - Numeric character references: `U+0000`, surrogates and code points above `U+10FFFF`
+///   become `U+FFFD`, and the `0x80`-`0x9F` range is remapped through the Windows-1252 C1
+///   table (eg. `&#128;` becomes `€` rather than the C1 control `U+0080`).
+/// - Named character references: the fixed set of legacy entities (`&amp`, `&lt`, `&copy`, ...)
+///   are recognized without a trailing `;`, consuming the longest matching prefix and leaving
+///   the rest of the input intact, same as a real HTML5 parser.
+///
+/// This matches how browsers parse real-world HTML, which routinely disagrees with the
+/// stricter XML-style rules used when `html5` is unset.
+///
+/// # Arguments
+/// - `reader` - UTF-8 encoded data is read from here.
+/// - `writer` - UTF8- decoded data is written to here.
+/// - `html5` - Apply the HTML5 numeric and legacy named character reference rules above.
+/// - `resolver` - Consulted for each named entity before the built-in table.
+///
+/// # Errors
+/// Errors can be caused by IO errors, `reader` producing invalid UTF-8, or by syntax errors.
+pub fn decode_html_rw_with<R: BufRead, W: Write, F>(
+    reader: R,
+    writer: &mut W,
+    sloppy: bool,
+    html5: bool,
+    mut resolver: F,
+) -> Result<(), DecodeErr>
+where
+    F: FnMut(&str) -> Option<Cow<str>>,
+{
     let mut state: DecodeState = Normal;
     let mut good_pos = 0;
     let mut buf = String::with_capacity(8);
@@ -114,96 +160,457 @@ pub fn decode_html_rw<R: BufRead, W: Write>(
             }
             Ok(c) => c,
         };
-        match state {
-            Normal if c == '&' => {
-                buf.push(c);
-                state = Entity
+        decode_step(
+            &mut state, &mut buf, &mut good_pos, pos, c, writer, sloppy, html5, &mut resolver,
+        )?;
+    }
+
+    decode_finish(state, &buf, good_pos, sloppy, html5, writer)
+}
+
+/// Finalizes a decode once there's no more input, flushing a pending legacy named entity (see
+/// the `Named if html5` branch of `decode_step`) before falling back to the `PrematureEnd`
+/// check. Shared by `decode_html_rw_with` and `HtmlDecoder::finish`.
+fn decode_finish<W: Write>(
+    state: DecodeState,
+    buf: &str,
+    good_pos: usize,
+    sloppy: bool,
+    html5: bool,
+    writer: &mut W,
+) -> Result<(), DecodeErr> {
+    if state == Named && html5 {
+        if let Some((matched_len, replacement)) = longest_legacy_match(&buf[1..]) {
+            try_dec_io!(write_char(writer, replacement), good_pos);
+            try_dec_io!(
+                writer.write_all(&buf.as_bytes()[1 + matched_len..]),
+                good_pos
+            );
+            return Ok(());
+        }
+    }
+
+    if state != Normal && !sloppy {
+        Err(DecodeErr {
+            position: good_pos,
+            kind: PrematureEnd,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Feeds a single decoded character through the entity state machine, writing any resulting
+/// output to `writer`. This is the shared core driving both `decode_html_rw_with` (fed from a
+/// whole `BufRead` in one pass) and `HtmlDecoder` (fed incrementally, chunk by chunk).
+#[allow(clippy::too_many_arguments)]
+fn decode_step<W: Write, F>(
+    state: &mut DecodeState,
+    buf: &mut String,
+    good_pos: &mut usize,
+    pos: usize,
+    c: char,
+    writer: &mut W,
+    sloppy: bool,
+    html5: bool,
+    resolver: &mut F,
+) -> Result<(), DecodeErr>
+where
+    F: FnMut(&str) -> Option<Cow<str>>,
+{
+    match *state {
+        Normal if c == '&' => {
+            buf.push(c);
+            *state = Entity
+        }
+        Normal => try_dec_io!(write_char(writer, c), *good_pos),
+        Entity if c == '#' => *state = Numeric,
+        Entity if c == ';' => {
+            if sloppy {
+                buf.clear();
+            } else {
+                return Err(DecodeErr {
+                    position: *good_pos,
+                    kind: UnknownEntity,
+                });
             }
-            Normal => try_dec_io!(write_char(writer, c), good_pos),
-            Entity if c == '#' => state = Numeric,
-            Entity if c == ';' => {
-                if sloppy {
-                    buf.clear();
-                } else {
-                    return Err(DecodeErr {
-                        position: good_pos,
-                        kind: UnknownEntity,
-                    });
+        }
+        Entity => {
+            *state = Named;
+            buf.push(c);
+        }
+        Named if c == ';' => {
+            buf.push(c);
+            *state = Normal;
+
+            let name = &buf[1..buf.len() - 1];
+            if let Some(replacement) = resolver(name) {
+                try_dec_io!(writer.write_all(replacement.as_bytes()), *good_pos);
+                buf.clear();
+            } else {
+                match decode_named_entity(buf) {
+                    Ok(res) => {
+                        try_dec_io!(writer.write_all(res.as_bytes()), *good_pos);
+                        buf.clear();
+                    }
+                    Err(reason) => {
+                        // `&name;` doesn't match the full-entity table, but in html5 mode
+                        // `name` might still match a legacy entity as a prefix (eg.
+                        // `&notZZZ;` matches `&not` with `ZZZ;` left over), same as when the
+                        // lookahead below finds the entity name never reaches a `;` at all.
+                        let legacy = if html5 { longest_legacy_match(name) } else { None };
+                        match legacy {
+                            Some((matched_len, replacement)) => {
+                                try_dec_io!(write_char(writer, replacement), *good_pos);
+                                let leftover = buf[1 + matched_len..].to_string();
+                                buf.clear();
+                                for rc in leftover.chars() {
+                                    try_dec_io!(write_char(writer, rc), *good_pos);
+                                }
+                            }
+                            None => {
+                                if sloppy {
+                                    try_dec_io!(writer.write_all(buf.as_bytes()), *good_pos);
+                                    buf.clear();
+                                } else {
+                                    return Err(DecodeErr {
+                                        position: *good_pos,
+                                        kind: reason,
+                                    });
+                                }
+                            }
+                        }
+                    }
                 }
             }
-            Entity => {
-                state = Named;
-                buf.push(c);
-            }
-            Named if c == ';' => {
-                buf.push(c);
-                state = Normal;
+        }
+        Named if html5 && !is_entity_name_char(c) => {
+            *state = Normal;
 
-                match decode_named_entity(&buf) {
-                    Err(reason) => {
+            let name = &buf[1..];
+            if let Some(replacement) = resolver(name) {
+                try_dec_io!(writer.write_all(replacement.as_bytes()), *good_pos);
+                buf.clear();
+            } else {
+                match longest_legacy_match(name) {
+                    Some((matched_len, replacement)) => {
+                        try_dec_io!(write_char(writer, replacement), *good_pos);
+                        let leftover = buf[1 + matched_len..].to_string();
+                        buf.clear();
+                        for rc in leftover.chars() {
+                            try_dec_io!(write_char(writer, rc), *good_pos);
+                        }
+                    }
+                    None => {
                         if sloppy {
-                            try_dec_io!(writer.write_all(buf.as_bytes()), good_pos);
+                            try_dec_io!(writer.write_all(buf.as_bytes()), *good_pos);
                             buf.clear();
                         } else {
                             return Err(DecodeErr {
-                                position: good_pos,
-                                kind: reason,
+                                position: *good_pos,
+                                kind: UnknownEntity,
                             });
                         }
                     }
-                    Ok(res) => {
-                        try_dec_io!(writer.write_all(res.as_bytes()), good_pos);
-                        buf.clear();
-                    }
                 }
             }
-            Named => buf.push(c),
-            Numeric if is_digit(c) => {
-                state = Dec;
+
+            // `c` was only a lookahead character used to find the end of the entity
+            // name; it wasn't consumed by the entity itself, so feed it back in as
+            // though it had just been seen in `Normal` state.
+            if c == '&' {
                 buf.push(c);
+                *state = Entity;
+            } else {
+                try_dec_io!(write_char(writer, c), *good_pos);
             }
-            Numeric if c == 'x' => state = Hex,
-            Dec if c == ';' => {
-                state = Normal;
-                let ch = try_parse!(decode_numeric(&buf[1..], 10), good_pos);
-                try_dec_io!(write_char(writer, ch), good_pos);
-                buf.clear();
+        }
+        Named => buf.push(c),
+        Numeric if is_digit(c) => {
+            *state = Dec;
+            buf.push(c);
+        }
+        Numeric if c == 'x' => *state = Hex,
+        Dec if c == ';' => {
+            *state = Normal;
+            let ch = if html5 {
+                try_parse!(decode_numeric_html5(&buf[1..], 10), *good_pos)
+            } else {
+                try_parse!(decode_numeric(&buf[1..], 10), *good_pos)
+            };
+            try_dec_io!(write_char(writer, ch), *good_pos);
+            buf.clear();
+        }
+        Hex if c == ';' => {
+            *state = Normal;
+            let ch = if html5 {
+                try_parse!(decode_numeric_html5(&buf[1..], 16), *good_pos)
+            } else {
+                try_parse!(decode_numeric(&buf[1..], 16), *good_pos)
+            };
+            try_dec_io!(write_char(writer, ch), *good_pos);
+            buf.clear();
+        }
+        Hex if is_hex_digit(c) => buf.push(c),
+        Dec if is_digit(c) => buf.push(c),
+        Numeric | Hex | Dec => {
+            if sloppy {
+                buf.clear()
+            } else {
+                return Err(DecodeErr {
+                    position: *good_pos,
+                    kind: MalformedNumEscape,
+                });
             }
-            Hex if c == ';' => {
-                state = Normal;
-                let ch = try_parse!(decode_numeric(&buf[1..], 16), good_pos);
-                try_dec_io!(write_char(writer, ch), good_pos);
-                buf.clear();
+        }
+    }
+
+    if *state == Normal {
+        *good_pos = pos + 1;
+    }
+
+    Ok(())
+}
+
+/// Incremental, chunk-fed HTML entity decoder.
+///
+/// Unlike `decode_html_rw`, which drives a whole reader-to-writer pass in one call, `HtmlDecoder`
+/// holds the entity state machine between calls to `push_str`, so input can be fed in arbitrary
+/// chunks - useful when reading from a socket, an async framework, or any other source where an
+/// entity may straddle a chunk boundary.
+///
+/// # Example
+/// ~~~
+/// use escaper::HtmlDecoder;
+///
+/// let mut decoder = HtmlDecoder::new();
+/// let mut out = Vec::new();
+/// decoder.push_str("&am", &mut out).unwrap();
+/// decoder.push_str("p;", &mut out).unwrap();
+/// decoder.finish(&mut out).unwrap();
+/// assert_eq!(out, b"&");
+/// ~~~
+pub struct HtmlDecoder {
+    state: DecodeState,
+    sloppy: bool,
+    html5: bool,
+    good_pos: usize,
+    pos: usize,
+    buf: String,
+}
+
+impl HtmlDecoder {
+    /// Creates a decoder using the same (non-sloppy, non-HTML5) rules as `decode_html`.
+    pub fn new() -> Self {
+        HtmlDecoder {
+            state: Normal,
+            sloppy: false,
+            html5: false,
+            good_pos: 0,
+            pos: 0,
+            buf: String::with_capacity(8),
+        }
+    }
+
+    /// Sets whether malformed entities are tolerated rather than reported as errors, as with
+    /// `decode_html_sloppy`: unknown named entities are written out unchanged, and malformed
+    /// numeric entities are dropped.
+    pub fn sloppy(mut self, sloppy: bool) -> Self {
+        self.sloppy = sloppy;
+        self
+    }
+
+    /// Sets whether numeric and legacy named entities follow the WHATWG HTML5 parsing rules,
+    /// as with `decode_html_html5`.
+    pub fn html5(mut self, html5: bool) -> Self {
+        self.html5 = html5;
+        self
+    }
+
+    /// Feeds a single character into the decoder, writing any resulting output to `out`.
+    pub fn push_char<W: Write>(&mut self, c: char, out: &mut W) -> Result<(), DecodeErr> {
+        decode_step(
+            &mut self.state,
+            &mut self.buf,
+            &mut self.good_pos,
+            self.pos,
+            c,
+            out,
+            self.sloppy,
+            self.html5,
+            &mut |_: &str| None,
+        )?;
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Feeds a chunk of text into the decoder, writing any resulting output to `out`. An
+    /// entity may straddle the boundary between two chunks; any partially-parsed entity is
+    /// retained internally until further chunks arrive or `finish` is called.
+    pub fn push_str<W: Write>(&mut self, chunk: &str, out: &mut W) -> Result<(), DecodeErr> {
+        for c in chunk.chars() {
+            self.push_char(c, out)?;
+        }
+        Ok(())
+    }
+
+    /// Signals that no more input is coming, writing any pending output (eg. a legacy named
+    /// entity that was still being matched) to `out`. Returns `PrematureEnd` if the decoder is
+    /// in the middle of an entity that can't be resolved this way and wasn't configured as
+    /// sloppy.
+    pub fn finish<W: Write>(self, out: &mut W) -> Result<(), DecodeErr> {
+        decode_finish(self.state, &self.buf, self.good_pos, self.sloppy, self.html5, out)
+    }
+
+    /// Adapts this decoder into a lazy iterator of decoded characters, pulling UTF-8 text out
+    /// of `reader` only as far as needed to produce the next character.
+    pub fn chars<R: BufRead + 'static>(self, reader: R) -> DecodedChars {
+        DecodedChars {
+            chars: Box::new(io_support::chars(reader)),
+            decoder: self,
+            pending: VecDeque::new(),
+            pos: 0,
+            done: false,
+        }
+    }
+
+    /// Adapts this decoder into a lazy `io::Read`, decoding entities as bytes are pulled
+    /// through it rather than all at once.
+    pub fn reader<R: BufRead + 'static>(self, reader: R) -> DecodedReader {
+        DecodedReader {
+            chars: self.chars(reader),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl Default for HtmlDecoder {
+    fn default() -> Self {
+        HtmlDecoder::new()
+    }
+}
+
+/// A lazy iterator of decoded characters, created by `HtmlDecoder::chars`.
+pub struct DecodedChars {
+    chars: Box<dyn Iterator<Item = Result<char, CharsError>>>,
+    decoder: HtmlDecoder,
+    pending: VecDeque<char>,
+    pos: usize,
+    done: bool,
+}
+
+impl Iterator for DecodedChars {
+    type Item = Result<char, DecodeErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(c) = self.pending.pop_front() {
+                return Some(Ok(c));
             }
-            Hex if is_hex_digit(c) => buf.push(c),
-            Dec if is_digit(c) => buf.push(c),
-            Numeric | Hex | Dec => {
-                if sloppy {
-                    buf.clear()
-                } else {
-                    return Err(DecodeErr {
-                        position: good_pos,
-                        kind: MalformedNumEscape,
-                    });
+            if self.done {
+                return None;
+            }
+            match self.chars.next() {
+                None => {
+                    self.done = true;
+                    let decoder = mem::take(&mut self.decoder);
+                    let mut out = Vec::new();
+                    if let Err(e) = decoder.finish(&mut out) {
+                        return Some(Err(e));
+                    }
+                    let out = String::from_utf8(out).expect("decoder output is valid UTF-8");
+                    self.pending.extend(out.chars());
+                }
+                Some(Err(e)) => {
+                    self.done = true;
+                    let kind = match e {
+                        CharsError::NotUtf8 => EncodingError,
+                        CharsError::Other(io) => IoError(io),
+                    };
+                    return Some(Err(DecodeErr {
+                        position: self.pos,
+                        kind,
+                    }));
+                }
+                Some(Ok(c)) => {
+                    let mut out = Vec::new();
+                    self.pos += 1;
+                    if let Err(e) = self.decoder.push_char(c, &mut out) {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                    let out = String::from_utf8(out).expect("decoder output is valid UTF-8");
+                    self.pending.extend(out.chars());
                 }
             }
         }
+    }
+}
+
+/// A lazy `io::Read` of decoded bytes, created by `HtmlDecoder::reader`.
+pub struct DecodedReader {
+    chars: DecodedChars,
+    pending: Vec<u8>,
+}
 
-        if state == Normal {
-            good_pos = pos + 1;
+impl io::Read for DecodedReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pending.is_empty() {
+            match self.chars.next() {
+                None => return Ok(0),
+                Some(Ok(c)) => {
+                    let mut tmp = [0u8; 4];
+                    let s = c.encode_utf8(&mut tmp);
+                    self.pending.extend_from_slice(s.as_bytes());
+                }
+                Some(Err(e)) => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))
+                }
+            }
         }
-    }
 
-    if state != Normal && !sloppy {
-        Err(DecodeErr {
-            position: good_pos,
-            kind: PrematureEnd,
-        })
-    } else {
-        Ok(())
+        let n = out.len().min(self.pending.len());
+        out[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
     }
 }
 
+/// Decodes an entity-encoded document from a reader whose bytes aren't necessarily UTF-8,
+/// transcoding them through `encoding_rs` according to the supplied charset `label` before
+/// running them through the entity state machine.
+///
+/// This keeps the pure-UTF-8 fast path in `decode_html_rw` untouched: only callers who opt
+/// into the `encoding` feature and call this function pay for the transcoding step, which is
+/// needed for the large body of legacy HTML served as `ISO-8859-1` or `windows-1252`.
+///
+/// # Arguments
+/// - `reader` - Data in the encoding named by `label` is read from here.
+/// - `writer` - UTF-8 decoded data is written to here.
+/// - `label` - A charset label as recognized by the WHATWG Encoding Standard (eg.
+///   `"windows-1252"`, `"iso-8859-1"`, `"utf-16"`).
+/// - `sloppy` - See `decode_html_rw`.
+///
+/// # Errors
+/// Returns `DecodeErrKind::EncodingError` if `label` isn't a recognized encoding, in addition
+/// to the errors `decode_html_rw` can return.
+#[cfg(feature = "encoding")]
+pub fn decode_html_rw_encoded<R: io::Read, W: Write>(
+    reader: R,
+    writer: &mut W,
+    label: &str,
+    sloppy: bool,
+) -> Result<(), DecodeErr> {
+    let encoding = encoding_rs::Encoding::for_label(label.as_bytes()).ok_or(DecodeErr {
+        position: 0,
+        kind: EncodingError,
+    })?;
+    let transcoded = encoding_rs_io::DecodeReaderBytesBuilder::new()
+        .encoding(Some(encoding))
+        .build(reader);
+    decode_html_rw(io::BufReader::new(transcoded), writer, sloppy)
+}
+
 /// Decodes an entity-encoded string.
 ///
 /// Decodes an entity encoded string, replacing HTML entities (`&amp;`, `&#20;` ...) with the
@@ -223,6 +630,30 @@ pub fn decode_html(s: &str) -> Result<String, DecodeErr> {
     decode_html_buf(s.as_bytes())
 }
 
+/// Decodes an entity-encoded string, borrowing the input unchanged when it contains no `&`.
+///
+/// Most HTML passed through a defensive decode step has no entities in it at all, so scanning
+/// for `&` up front and returning the original string when it's absent saves a `String`
+/// allocation and copy that `decode_html` would otherwise pay for on every call.
+///
+/// # Example
+/// ~~~
+/// use std::borrow::Cow;
+///
+/// assert_eq!(escaper::decode_html_cow("hello").unwrap(), Cow::Borrowed("hello"));
+/// assert_eq!(
+///     escaper::decode_html_cow("&lt;b&gt;").unwrap(),
+///     Cow::<str>::Owned("<b>".to_string())
+/// );
+/// ~~~
+pub fn decode_html_cow(s: &str) -> Result<Cow<'_, str>, DecodeErr> {
+    if s.contains('&') {
+        decode_html(s).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(s))
+    }
+}
+
 pub fn decode_html_sloppy(s: &str) -> Result<String, DecodeErr> {
     decode_html_buf_sloppy(s.as_bytes())
 }
@@ -251,6 +682,68 @@ pub fn decode_html_buf_sloppy(buf: impl AsRef<[u8]>) -> Result<String, DecodeErr
     }
 }
 
+/// Decodes an entity-encoded string using HTML5-conformant parsing rules (see
+/// `decode_html_rw_with`) instead of the stricter rules used by `decode_html`.
+pub fn decode_html_html5(s: &str) -> Result<String, DecodeErr> {
+    decode_html_buf_html5(s.as_bytes())
+}
+
+pub fn decode_html_buf_html5(buf: impl AsRef<[u8]>) -> Result<String, DecodeErr> {
+    let buf = buf.as_ref();
+    let mut writer = Vec::with_capacity(buf.len());
+    let mut reader = Cursor::new(buf);
+
+    let res = decode_html_rw_with(&mut reader, &mut writer, false, true, |_: &str| None);
+    match res {
+        Ok(_) => Ok(String::from_utf8(writer).unwrap()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Decodes an entity-encoded string, consulting a custom resolver for named entities before
+/// falling back to the built-in table.
+///
+/// See `decode_html_rw_with` for details on `resolver`. Like `decode_html`, this is not
+/// sloppy: unresolved entities are reported as errors.
+///
+/// # Arguments
+/// - `s` - Entity-encoded string to decode.
+/// - `resolver` - Consulted for each named entity before the built-in table.
+///
+/// # Example
+/// ~~~
+/// use std::borrow::Cow;
+///
+/// let decoded = escaper::decode_html_with("&company; makes &amp; sells widgets", |name: &str| {
+///     match name {
+///         "company" => Some(Cow::Borrowed("Acme")),
+///         _ => None,
+///     }
+/// }).unwrap();
+/// assert_eq!(&decoded, "Acme makes & sells widgets");
+/// ~~~
+pub fn decode_html_with<F>(s: &str, resolver: F) -> Result<String, DecodeErr>
+where
+    F: FnMut(&str) -> Option<Cow<str>>,
+{
+    decode_html_buf_with(s.as_bytes(), resolver)
+}
+
+pub fn decode_html_buf_with<F>(buf: impl AsRef<[u8]>, resolver: F) -> Result<String, DecodeErr>
+where
+    F: FnMut(&str) -> Option<Cow<str>>,
+{
+    let buf = buf.as_ref();
+    let mut writer = Vec::with_capacity(buf.len());
+    let mut reader = Cursor::new(buf);
+
+    let res = decode_html_rw_with(&mut reader, &mut writer, false, false, resolver);
+    match res {
+        Ok(_) => Ok(String::from_utf8(writer).unwrap()),
+        Err(err) => Err(err),
+    }
+}
+
 fn is_digit(c: char) -> bool {
     c >= '0' && c <= '9'
 }
@@ -259,6 +752,141 @@ fn is_hex_digit(c: char) -> bool {
     is_digit(c) || (c >= 'a' && c <= 'f') || (c >= 'A' && c <= 'F')
 }
 
+fn is_entity_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+}
+
+/// Matches the longest prefix of `name` (the characters seen so far between `&` and the first
+/// non-name character, with no trailing `;`) against the fixed set of legacy HTML5 entities
+/// that are recognized without a terminating semicolon.
+///
+/// Returns the byte length of the matched prefix and its replacement, so that any remaining
+/// characters in `name` can be fed back into the decoder unchanged.
+fn longest_legacy_match(name: &str) -> Option<(usize, char)> {
+    (1..=name.len())
+        .rev()
+        .find_map(|len| {
+            LEGACY_ENTITIES
+                .iter()
+                .find(|&&(entity, _)| entity == &name[..len])
+                .map(|&(_, replacement)| (len, replacement))
+        })
+}
+
+/// The HTML5 legacy named character references that are recognized without a trailing `;`,
+/// per the WHATWG HTML parsing spec. This is the historical HTML4 Latin-1 entity set plus the
+/// four markup entities (`amp`, `lt`, `gt`, `quot`) and the uppercase aliases `AMP`, `LT`,
+/// `GT`, `QUOT`, `COPY` and `REG`, kept around for compatibility with documents that predate
+/// the semicolon requirement.
+static LEGACY_ENTITIES: [(&str, char); 106] = [
+    ("amp", '&'),
+    ("AMP", '&'),
+    ("lt", '<'),
+    ("LT", '<'),
+    ("gt", '>'),
+    ("GT", '>'),
+    ("quot", '"'),
+    ("QUOT", '"'),
+    ("copy", '\u{00A9}'),
+    ("COPY", '\u{00A9}'),
+    ("reg", '\u{00AE}'),
+    ("REG", '\u{00AE}'),
+    ("nbsp", '\u{00A0}'),
+    ("iexcl", '\u{00A1}'),
+    ("cent", '\u{00A2}'),
+    ("pound", '\u{00A3}'),
+    ("curren", '\u{00A4}'),
+    ("yen", '\u{00A5}'),
+    ("brvbar", '\u{00A6}'),
+    ("sect", '\u{00A7}'),
+    ("uml", '\u{00A8}'),
+    ("ordf", '\u{00AA}'),
+    ("laquo", '\u{00AB}'),
+    ("not", '\u{00AC}'),
+    ("shy", '\u{00AD}'),
+    ("macr", '\u{00AF}'),
+    ("deg", '\u{00B0}'),
+    ("plusmn", '\u{00B1}'),
+    ("sup2", '\u{00B2}'),
+    ("sup3", '\u{00B3}'),
+    ("acute", '\u{00B4}'),
+    ("micro", '\u{00B5}'),
+    ("para", '\u{00B6}'),
+    ("middot", '\u{00B7}'),
+    ("cedil", '\u{00B8}'),
+    ("sup1", '\u{00B9}'),
+    ("ordm", '\u{00BA}'),
+    ("raquo", '\u{00BB}'),
+    ("frac14", '\u{00BC}'),
+    ("frac12", '\u{00BD}'),
+    ("frac34", '\u{00BE}'),
+    ("iquest", '\u{00BF}'),
+    ("Agrave", '\u{00C0}'),
+    ("Aacute", '\u{00C1}'),
+    ("Acirc", '\u{00C2}'),
+    ("Atilde", '\u{00C3}'),
+    ("Auml", '\u{00C4}'),
+    ("Aring", '\u{00C5}'),
+    ("AElig", '\u{00C6}'),
+    ("Ccedil", '\u{00C7}'),
+    ("Egrave", '\u{00C8}'),
+    ("Eacute", '\u{00C9}'),
+    ("Ecirc", '\u{00CA}'),
+    ("Euml", '\u{00CB}'),
+    ("Igrave", '\u{00CC}'),
+    ("Iacute", '\u{00CD}'),
+    ("Icirc", '\u{00CE}'),
+    ("Iuml", '\u{00CF}'),
+    ("ETH", '\u{00D0}'),
+    ("Ntilde", '\u{00D1}'),
+    ("Ograve", '\u{00D2}'),
+    ("Oacute", '\u{00D3}'),
+    ("Ocirc", '\u{00D4}'),
+    ("Otilde", '\u{00D5}'),
+    ("Ouml", '\u{00D6}'),
+    ("times", '\u{00D7}'),
+    ("Oslash", '\u{00D8}'),
+    ("Ugrave", '\u{00D9}'),
+    ("Uacute", '\u{00DA}'),
+    ("Ucirc", '\u{00DB}'),
+    ("Uuml", '\u{00DC}'),
+    ("Yacute", '\u{00DD}'),
+    ("THORN", '\u{00DE}'),
+    ("szlig", '\u{00DF}'),
+    ("agrave", '\u{00E0}'),
+    ("aacute", '\u{00E1}'),
+    ("acirc", '\u{00E2}'),
+    ("atilde", '\u{00E3}'),
+    ("auml", '\u{00E4}'),
+    ("aring", '\u{00E5}'),
+    ("aelig", '\u{00E6}'),
+    ("ccedil", '\u{00E7}'),
+    ("egrave", '\u{00E8}'),
+    ("eacute", '\u{00E9}'),
+    ("ecirc", '\u{00EA}'),
+    ("euml", '\u{00EB}'),
+    ("igrave", '\u{00EC}'),
+    ("iacute", '\u{00ED}'),
+    ("icirc", '\u{00EE}'),
+    ("iuml", '\u{00EF}'),
+    ("eth", '\u{00F0}'),
+    ("ntilde", '\u{00F1}'),
+    ("ograve", '\u{00F2}'),
+    ("oacute", '\u{00F3}'),
+    ("ocirc", '\u{00F4}'),
+    ("otilde", '\u{00F5}'),
+    ("ouml", '\u{00F6}'),
+    ("divide", '\u{00F7}'),
+    ("oslash", '\u{00F8}'),
+    ("ugrave", '\u{00F9}'),
+    ("uacute", '\u{00FA}'),
+    ("ucirc", '\u{00FB}'),
+    ("uuml", '\u{00FC}'),
+    ("yacute", '\u{00FD}'),
+    ("thorn", '\u{00FE}'),
+    ("yuml", '\u{00FF}'),
+];
+
 fn decode_named_entity(entity: &str) -> Result<&'static str, DecodeErrKind> {
     match entities::ENTITIES.iter().find(|e| e.entity == entity) {
         None => Err(UnknownEntity),
@@ -275,3 +903,54 @@ fn decode_numeric(esc: &str, radix: u32) -> Result<char, DecodeErrKind> {
         Err(..) => Err(MalformedNumEscape),
     }
 }
+
+/// Like `decode_numeric`, but follows the WHATWG HTML5 parsing rules for numeric character
+/// references instead of rejecting out-of-range code points: `U+0000`, surrogates and code
+/// points above `U+10FFFF` are replaced with `U+FFFD`, and `0x80`-`0x9F` is remapped through
+/// the Windows-1252 C1 control table that browsers apply for legacy compatibility.
+fn decode_numeric_html5(esc: &str, radix: u32) -> Result<char, DecodeErrKind> {
+    match u32::from_str_radix(esc, radix) {
+        Ok(n) => Ok(html5_numeric_remap(n)),
+        // Too large for a `u32`, so necessarily above `U+10FFFF`.
+        Err(ref e) if *e.kind() == std::num::IntErrorKind::PosOverflow => Ok('\u{FFFD}'),
+        Err(..) => Err(MalformedNumEscape),
+    }
+}
+
+fn html5_numeric_remap(n: u32) -> char {
+    match n {
+        0x00 | 0xD800..=0xDFFF => '\u{FFFD}',
+        n if n > 0x10FFFF => '\u{FFFD}',
+        0x80 => '\u{20AC}',
+        0x82 => '\u{201A}',
+        0x83 => '\u{0192}',
+        0x84 => '\u{201E}',
+        0x85 => '\u{2026}',
+        0x86 => '\u{2020}',
+        0x87 => '\u{2021}',
+        0x88 => '\u{02C6}',
+        0x89 => '\u{2030}',
+        0x8A => '\u{0160}',
+        0x8B => '\u{2039}',
+        0x8C => '\u{0152}',
+        0x8E => '\u{017D}',
+        0x91 => '\u{2018}',
+        0x92 => '\u{2019}',
+        0x93 => '\u{201C}',
+        0x94 => '\u{201D}',
+        0x95 => '\u{2022}',
+        0x96 => '\u{2013}',
+        0x97 => '\u{2014}',
+        0x98 => '\u{02DC}',
+        0x99 => '\u{2122}',
+        0x9A => '\u{0161}',
+        0x9B => '\u{203A}',
+        0x9C => '\u{0153}',
+        0x9E => '\u{017E}',
+        0x9F => '\u{0178}',
+        // The remaining C1 code points have no Windows-1252 mapping and fall back to U+FFFD,
+        // same as any other unmapped code point in the range.
+        0x81 | 0x8D | 0x8F | 0x90 | 0x9D => '\u{FFFD}',
+        _ => char::from_u32(n).expect("non-surrogate, in-range code point"),
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::char;
 use std::io::{self, Write};
 
@@ -62,6 +63,29 @@ pub fn encode_minimal_w<W: Write>(s: &str, writer: &mut W) -> io::Result<()> {
     Ok(())
 }
 
+///
+/// HTML entity-encode a string, borrowing the input unchanged when it contains none of the
+/// minimal set of entities.
+///
+/// `encode_minimal` always builds a fresh output buffer, even for text that has nothing to
+/// escape. Checking first and returning the input unchanged in that case lets callers escape
+/// untrusted content on every pass without paying for a copy on the common, already-safe input.
+///
+/// # Example
+/// ~~~
+/// use std::borrow::Cow;
+///
+/// assert_eq!(escaper::encode_minimal_cow("hello"), Cow::Borrowed("hello"));
+/// assert_eq!(escaper::encode_minimal_cow("<b>"), Cow::<str>::Owned("&lt;b&gt;".to_string()));
+/// ~~~
+pub fn encode_minimal_cow(s: &str) -> Cow<'_, str> {
+    if s.chars().any(|c| get_entity(c).is_some()) {
+        Cow::Owned(encode_minimal(s))
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
 ///
 /// HTML entity-encodes a string for use in attributes values.
 ///
@@ -1,4 +1,8 @@
 extern crate entities;
+#[cfg(feature = "encoding")]
+extern crate encoding_rs;
+#[cfg(feature = "encoding")]
+extern crate encoding_rs_io;
 
 pub use decode::*;
 pub use encode::*;